@@ -0,0 +1,1451 @@
+//! This is the library backing the `cargo sync-readme` command. It is not meant to be used as a
+//! standalone dependency — its API has no stability guarantee and only exists to be consumed by
+//! the `cargo-sync-readme` binary. Have a look at the binary’s documentation for the actual user
+//! facing documentation of the tool.
+
+use regex::Regex;
+use serde_derive::Deserialize;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Marker used to tell `cargo sync-readme` where to insert the synchronized documentation.
+pub const SYNC_MARKER: &str = "<!-- cargo-sync-readme -->";
+
+/// Marker automatically inserted to delimit the beginning of the synchronized documentation.
+pub const SYNC_START_MARKER: &str = "<!-- cargo-sync-readme start -->";
+
+/// Marker automatically inserted to delimit the end of the synchronized documentation.
+pub const SYNC_END_MARKER: &str = "<!-- cargo-sync-readme end -->";
+
+/// Possible errors that might happen while synchronizing a README.
+#[derive(Debug)]
+pub enum Error {
+  CannotFindManifest,
+  CannotParseManifest(toml::de::Error),
+  CannotOpenFile(PathBuf, std::io::Error),
+  MissingMarkers,
+  MarkersMismatch,
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      Error::CannotFindManifest => write!(
+        f,
+        "cannot find a Cargo.toml manifest in the current directory or any of its parents"
+      ),
+      Error::CannotParseManifest(ref e) => write!(f, "cannot parse Cargo.toml manifest: {}", e),
+      Error::CannotOpenFile(ref path, ref e) => {
+        write!(f, "cannot open file {}: {}", path.display(), e)
+      }
+      Error::MissingMarkers => write!(
+        f,
+        "cannot find the {} marker in the README",
+        SYNC_MARKER
+      ),
+      Error::MarkersMismatch => write!(
+        f,
+        "the {} marker comes after the {} marker in the README",
+        SYNC_START_MARKER, SYNC_END_MARKER
+      ),
+    }
+  }
+}
+
+impl error::Error for Error {}
+
+/// Which entry point to read the front-page documentation from, in the case a crate has both a
+/// library and a binary target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreferDocFrom {
+  Bin,
+  Lib,
+}
+
+impl FromStr for PreferDocFrom {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "bin" => Ok(PreferDocFrom::Bin),
+      "lib" => Ok(PreferDocFrom::Lib),
+      _ => Err(format!("unknown value {}; expected `bin` or `lib`", s)),
+    }
+  }
+}
+
+/// How to report a README that turns out to be out of sync under `--check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+  /// A line-level unified diff, with context lines.
+  Unified,
+  /// A one-line summary of how many lines were added/removed.
+  Brief,
+  /// No diagnostic at all; only the exit code reflects the out-of-sync status.
+  None,
+}
+
+impl FromStr for DiffFormat {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "unified" => Ok(DiffFormat::Unified),
+      "brief" => Ok(DiffFormat::Brief),
+      "none" => Ok(DiffFormat::None),
+      _ => Err(format!("unknown value {}; expected `unified`, `brief` or `none`", s)),
+    }
+  }
+}
+
+#[derive(Debug, Deserialize)]
+struct Package {
+  name: String,
+  readme: Option<String>,
+  version: Option<String>,
+  license: Option<String>,
+  #[serde(default)]
+  authors: Vec<String>,
+  repository: Option<String>,
+  #[serde(rename = "rust-version")]
+  rust_version: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Workspace {
+  #[serde(default)]
+  members: Vec<String>,
+}
+
+/// In-memory representation of a `Cargo.toml` manifest, restricted to the fields this tool cares
+/// about.
+///
+/// A manifest is either a regular (possibly workspace member) manifest, holding a `[package]`
+/// section, or a *virtual* manifest, holding only a `[workspace]` section and no package of its
+/// own.
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+  package: Option<Package>,
+  workspace: Option<Workspace>,
+
+  #[serde(skip)]
+  dir: PathBuf,
+}
+
+impl Manifest {
+  /// Find the `Cargo.toml` manifest, walking up parent directories starting at `pwd`.
+  pub fn find_manifest(pwd: impl Into<PathBuf>) -> Result<Self, Error> {
+    let mut dir = pwd.into();
+
+    loop {
+      let manifest_path = dir.join("Cargo.toml");
+
+      if manifest_path.is_file() {
+        return Self::from_path(manifest_path, dir);
+      }
+
+      if !dir.pop() {
+        return Err(Error::CannotFindManifest);
+      }
+    }
+  }
+
+  fn from_path(manifest_path: PathBuf, dir: PathBuf) -> Result<Self, Error> {
+    let content = fs::read_to_string(&manifest_path)
+      .map_err(|e| Error::CannotOpenFile(manifest_path, e))?;
+    let mut manifest: Manifest = toml::from_str(&content).map_err(Error::CannotParseManifest)?;
+
+    manifest.dir = dir;
+
+    Ok(manifest)
+  }
+
+  /// Whether this manifest is a virtual manifest (i.e. a workspace root with no `[package]`).
+  pub fn is_virtual(&self) -> bool {
+    self.package.is_none() && self.workspace.is_some()
+  }
+
+  /// The directory containing this manifest.
+  pub fn dir(&self) -> &Path {
+    &self.dir
+  }
+
+  /// Resolve the manifests of every member of the workspace declared by this manifest’s own
+  /// `[workspace]` section, including this manifest itself when it is a hybrid workspace root (one
+  /// that has both a `[package]` and a `[workspace]` section, and so is itself a crate of the
+  /// workspace).
+  ///
+  /// A member path of the common `some/dir/*` shape is expanded to every immediate subdirectory
+  /// of `some/dir` that holds a `Cargo.toml`; other glob shapes (`**`, a `*` in the middle of a
+  /// path, …) aren’t supported and are joined literally, same as before.
+  ///
+  /// If this manifest has no `[workspace]` section, an empty vector is returned. This includes the
+  /// manifest of a workspace member: the parent directories are not searched for the workspace it
+  /// belongs to.
+  pub fn members(&self) -> Result<Vec<Manifest>, Error> {
+    let members = match self.workspace {
+      Some(ref workspace) => &workspace.members,
+      None => return Ok(Vec::new()),
+    };
+
+    let mut manifests = Vec::new();
+
+    if self.package.is_some() {
+      manifests.push(Self::from_path(self.dir.join("Cargo.toml"), self.dir.clone())?);
+    }
+
+    for member in members {
+      for member_dir in self.expand_member_dirs(member) {
+        let member_manifest_path = member_dir.join("Cargo.toml");
+
+        manifests.push(Self::from_path(member_manifest_path, member_dir)?);
+      }
+    }
+
+    Ok(manifests)
+  }
+
+  /// Expand a single `[workspace] members` entry into the directories it designates.
+  ///
+  /// A trailing `*` path component (e.g. `crates/*`) is expanded to every immediate subdirectory
+  /// of its parent that holds a `Cargo.toml`. Anything else is returned as-is, joined onto this
+  /// manifest’s directory, same as a plain non-glob member.
+  fn expand_member_dirs(&self, member: &str) -> Vec<PathBuf> {
+    match member.strip_suffix("/*").or_else(|| member.strip_suffix("\\*")) {
+      Some(glob_base) => {
+        let glob_dir = self.dir.join(glob_base);
+        let mut entries: Vec<PathBuf> = fs::read_dir(&glob_dir)
+          .into_iter()
+          .flatten()
+          .filter_map(Result::ok)
+          .map(|entry| entry.path())
+          .filter(|path| path.is_dir() && path.join("Cargo.toml").is_file())
+          .collect();
+
+        entries.sort();
+        entries
+      }
+      None => vec![self.dir.join(member)],
+    }
+  }
+
+  pub fn crate_name(&self) -> Option<String> {
+    self.package.as_ref().map(|p| p.name.clone())
+  }
+
+  /// The `version` field of the `[package]` section, if any.
+  pub fn crate_version(&self) -> Option<&str> {
+    self.package.as_ref()?.version.as_deref()
+  }
+
+  /// The `license` field of the `[package]` section, if any.
+  pub fn crate_license(&self) -> Option<&str> {
+    self.package.as_ref()?.license.as_deref()
+  }
+
+  /// The `authors` field of the `[package]` section.
+  pub fn crate_authors(&self) -> &[String] {
+    self.package.as_ref().map_or(&[], |p| p.authors.as_slice())
+  }
+
+  /// The `repository` field of the `[package]` section, if any.
+  pub fn crate_repository(&self) -> Option<&str> {
+    self.package.as_ref()?.repository.as_deref()
+  }
+
+  /// The `rust-version` (MSRV) field of the `[package]` section, if any.
+  pub fn crate_msrv(&self) -> Option<&str> {
+    self.package.as_ref()?.rust_version.as_deref()
+  }
+
+  pub fn readme(&self) -> PathBuf {
+    self
+      .package
+      .as_ref()
+      .and_then(|p| p.readme.as_ref())
+      .map(|readme| self.dir.join(readme))
+      .unwrap_or_else(|| self.dir.join("README.md"))
+  }
+
+  pub fn entry_point(&self, prefer_doc_from: Option<PreferDocFrom>) -> Option<PathBuf> {
+    let lib = self.dir.join("src/lib.rs");
+    let bin = self.dir.join("src/main.rs");
+
+    match prefer_doc_from {
+      Some(PreferDocFrom::Lib) => Some(lib).filter(|p| p.is_file()),
+      Some(PreferDocFrom::Bin) => Some(bin).filter(|p| p.is_file()),
+      None => match (lib.is_file(), bin.is_file()) {
+        (true, false) => Some(lib),
+        (false, true) => Some(bin),
+        _ => None,
+      },
+    }
+  }
+}
+
+/// Turn a Rust string literal’s content (as found between the quotes) into the string it denotes,
+/// handling the handful of escape sequences likely to show up in doc attributes.
+fn unescape_rust_string(literal: &str) -> String {
+  let mut out = String::with_capacity(literal.len());
+  let mut chars = literal.chars();
+
+  while let Some(c) = chars.next() {
+    if c != '\\' {
+      out.push(c);
+      continue;
+    }
+
+    match chars.next() {
+      Some('n') => out.push('\n'),
+      Some('t') => out.push('\t'),
+      Some('"') => out.push('"'),
+      Some('\\') => out.push('\\'),
+      Some(other) => out.push(other),
+      None => {}
+    }
+  }
+
+  out
+}
+
+/// Split a `cfg` predicate's argument list on its top-level commas, ignoring commas nested inside
+/// parentheses (e.g. a nested `any(⋯)`) or inside a string literal.
+fn split_top_level_commas(s: &str) -> Vec<&str> {
+  let mut parts = Vec::new();
+  let mut depth = 0;
+  let mut in_quote = false;
+  let mut start = 0;
+
+  for (i, c) in s.char_indices() {
+    match c {
+      '"' => in_quote = !in_quote,
+      '(' if !in_quote => depth += 1,
+      ')' if !in_quote => depth -= 1,
+      ',' if !in_quote && depth == 0 => {
+        parts.push(s[start..i].trim());
+        start = i + 1;
+      }
+      _ => {}
+    }
+  }
+
+  parts.push(s[start..].trim());
+  parts
+}
+
+/// Whether a `cfg`/`cfg_attr` predicate is active given `active_cfgs`, recursively handling
+/// `any(⋯)`, `all(⋯)` and `not(⋯)` combinators so that e.g. `any(feature = "a", feature = "b")`
+/// is active as soon as one of its branches names an active cfg.
+fn predicate_is_active(predicate: &str, active_cfgs: &[String]) -> bool {
+  let predicate = predicate.trim();
+
+  if let Some(inner) = predicate.strip_prefix("any(").and_then(|s| s.strip_suffix(')')) {
+    split_top_level_commas(inner).iter().any(|p| predicate_is_active(p, active_cfgs))
+  } else if let Some(inner) = predicate.strip_prefix("all(").and_then(|s| s.strip_suffix(')')) {
+    split_top_level_commas(inner).iter().all(|p| predicate_is_active(p, active_cfgs))
+  } else if let Some(inner) = predicate.strip_prefix("not(").and_then(|s| s.strip_suffix(')')) {
+    !predicate_is_active(inner, active_cfgs)
+  } else {
+    active_cfgs
+      .iter()
+      .any(|cfg| predicate == cfg.as_str() || predicate == format!("feature = \"{}\"", cfg))
+  }
+}
+
+/// Extract the inner documentation of a Rust source file: `//!` line comments as well as
+/// `#![doc = "⋯"]` and `#![cfg_attr(⋯, doc = "⋯")]` attributes, in source order.
+pub fn extract_inner_doc(path: impl AsRef<Path>, show_hidden_doc: bool, crlf: bool) -> String {
+  extract_inner_doc_with_cfg(path, show_hidden_doc, crlf, &[])
+}
+
+/// Like [`extract_inner_doc`], but `active_cfgs` controls which `#![cfg_attr(⋯, doc = "⋯")]`
+/// predicates are considered active. A `cfg_attr` is included when `active_cfgs` is empty (the
+/// default: every `cfg_attr` doc is included unconditionally) or when its predicate holds with
+/// exactly the given cfgs active (e.g. `feature = "x"` holds for `"x"`; `any(⋯)`, `all(⋯)` and
+/// `not(⋯)` are evaluated accordingly).
+pub fn extract_inner_doc_with_cfg(
+  path: impl AsRef<Path>,
+  show_hidden_doc: bool,
+  crlf: bool,
+  active_cfgs: &[String],
+) -> String {
+  let path = path.as_ref();
+  let content = fs::read_to_string(path).unwrap_or_default();
+  let dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+  let doc_attr_re = Regex::new(r#"^#!\[\s*doc\s*=\s*"((?:[^"\\]|\\.)*)"\s*\]$"#).unwrap();
+  let include_str_re =
+    Regex::new(r#"^#!\[\s*doc\s*=\s*include_str!\(\s*"([^"]+)"\s*\)\s*\]$"#).unwrap();
+  // The predicate is captured greedily (rather than stopping at the first comma) so that a
+  // predicate containing its own commas, e.g. `any(feature = "a", feature = "b")`, isn't mistaken
+  // for the top-level separator before `doc = "⋯"`.
+  let cfg_attr_re =
+    Regex::new(r#"^#!\[\s*cfg_attr\(\s*(.+)\s*,\s*doc\s*=\s*"((?:[^"\\]|\\.)*)"\s*\)\s*\]$"#)
+      .unwrap();
+
+  let mut lines = Vec::new();
+  let push_doc_text = |text: &str, lines: &mut Vec<String>| {
+    for doc_line in text.split('\n') {
+      if !show_hidden_doc && doc_line.trim_start().starts_with("# ") {
+        continue;
+      }
+
+      lines.push(doc_line.to_owned());
+    }
+  };
+
+  for line in content.lines() {
+    let trimmed_start = line.trim_start();
+
+    if let Some(doc) = trimmed_start.strip_prefix("//!") {
+      let doc = doc.strip_prefix(' ').unwrap_or(doc);
+
+      if !show_hidden_doc && doc.trim_start().starts_with("# ") {
+        continue;
+      }
+
+      lines.push(doc.to_owned());
+      continue;
+    }
+
+    // Attributes, unlike `//!` lines, are matched against a fully-trimmed line: the `$` anchor in
+    // each regex must reach the end of meaningful content regardless of any trailing whitespace.
+    let trimmed = line.trim();
+
+    if let Some(caps) = include_str_re.captures(trimmed) {
+      let included_path = dir.join(&caps[1]);
+      let included = fs::read_to_string(included_path).unwrap_or_default();
+
+      push_doc_text(&included, &mut lines);
+    } else if let Some(caps) = doc_attr_re.captures(trimmed) {
+      push_doc_text(&unescape_rust_string(&caps[1]), &mut lines);
+    } else if let Some(caps) = cfg_attr_re.captures(trimmed) {
+      let predicate = caps[1].trim();
+      let is_active = active_cfgs.is_empty() || predicate_is_active(predicate, active_cfgs);
+
+      if is_active {
+        push_doc_text(&unescape_rust_string(&caps[2]), &mut lines);
+      }
+    } else if lines.is_empty() {
+      continue;
+    } else {
+      break;
+    }
+  }
+
+  let newline = if crlf { "\r\n" } else { "\n" };
+
+  lines.join(newline)
+}
+
+/// Read the content of a README file.
+pub fn read_readme(path: impl AsRef<Path>) -> Result<String, Error> {
+  fs::read_to_string(path.as_ref()).map_err(|e| Error::CannotOpenFile(path.as_ref().to_owned(), e))
+}
+
+/// The kind of a Rust item, as far as building a docs.rs URL is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ItemKind {
+  Struct,
+  Enum,
+  Trait,
+  Fn,
+  Type,
+  Mod,
+}
+
+impl ItemKind {
+  fn page_prefix(self) -> &'static str {
+    match self {
+      ItemKind::Struct => "struct.",
+      ItemKind::Enum => "enum.",
+      ItemKind::Trait => "trait.",
+      ItemKind::Fn => "fn.",
+      ItemKind::Type => "type.",
+      ItemKind::Mod => "",
+    }
+  }
+
+  /// The docs.rs page file name for an item called `item_name`, e.g. `struct.Foo.html` or, for a
+  /// module, `foo/index.html` (modules get their own directory rather than a `mod.` page).
+  fn page_file(self, item_name: &str) -> String {
+    match self {
+      ItemKind::Mod => format!("{}/index.html", item_name),
+      _ => format!("{}{}.html", self.page_prefix(), item_name),
+    }
+  }
+}
+
+/// A minimal index of the items a crate exposes publicly, built by scanning its entry point.
+///
+/// This is only good enough to resolve intra-doc links; it is not a replacement for an actual
+/// Rust parser.
+struct ItemIndex {
+  items: std::collections::HashMap<String, ItemKind>,
+  assoc_consts: std::collections::HashSet<String>,
+}
+
+impl ItemIndex {
+  fn from_source(source: &str) -> Self {
+    let mut items = std::collections::HashMap::new();
+    let decl_re = Regex::new(
+      r"(?m)^\s*pub\s+(?:\([^)]*\)\s+)?(struct|enum|trait|fn|type|mod)\s+([A-Za-z_][A-Za-z0-9_]*)",
+    )
+    .unwrap();
+    let use_re = Regex::new(r"(?m)^\s*pub\s+use\s+[A-Za-z0-9_:]*::([A-Za-z_][A-Za-z0-9_]*)\s*;")
+      .unwrap();
+    // Associated constants follow Rust's SCREAMING_SNAKE_CASE convention, which is the only way
+    // we can tell them apart from a method with this regex-based index (we don't track which
+    // impl/trait block a `const` sits in, only that it looks like an associated one).
+    let assoc_const_re =
+      Regex::new(r"(?m)^\s*(?:pub\s+)?const\s+([A-Z][A-Z0-9_]*)\s*:").unwrap();
+
+    for caps in decl_re.captures_iter(source) {
+      let kind = match &caps[1] {
+        "struct" => ItemKind::Struct,
+        "enum" => ItemKind::Enum,
+        "trait" => ItemKind::Trait,
+        "fn" => ItemKind::Fn,
+        "type" => ItemKind::Type,
+        "mod" => ItemKind::Mod,
+        _ => unreachable!(),
+      };
+
+      items.insert(caps[2].to_owned(), kind);
+    }
+
+    // Re-exports (`pub use foo::Bar;`) don’t tell us the kind of the re-exported item, so we
+    // conservatively assume it behaves like a struct, the most common case; unresolved lookups
+    // still fall back to a warning rather than a wrong link.
+    for caps in use_re.captures_iter(source) {
+      items.entry(caps[1].to_owned()).or_insert(ItemKind::Struct);
+    }
+
+    let assoc_consts = assoc_const_re.captures_iter(source).map(|caps| caps[1].to_owned()).collect();
+
+    ItemIndex { items, assoc_consts }
+  }
+
+  /// Whether `name` looks like an associated constant (as opposed to a method), based on it being
+  /// declared as a `const` in the entry point and following the SCREAMING_SNAKE_CASE convention.
+  fn is_associated_const(&self, name: &str) -> bool {
+    self.assoc_consts.contains(name)
+  }
+
+  fn resolve<'a>(&self, segments: &[&'a str]) -> Option<(ItemKind, Vec<&'a str>)> {
+    let last = *segments.last()?;
+
+    if let Some(&kind) = self.items.get(last) {
+      return Some((kind, segments.to_vec()));
+    }
+
+    // Not found as a top-level item: maybe the last segment is a method/associated item and the
+    // parent segment is the actual type.
+    if segments.len() >= 2 {
+      let parent = segments[segments.len() - 2];
+
+      if let Some(&kind) = self.items.get(parent) {
+        return Some((kind, segments[..segments.len() - 1].to_vec()));
+      }
+    }
+
+    None
+  }
+}
+
+/// Whether a markdown link destination looks like a Rust item path rather than a regular URL,
+/// file path or anchor.
+fn looks_like_intralink(dest: &str) -> bool {
+  !dest.is_empty()
+    && !dest.contains("://")
+    && !dest.contains('/')
+    && !dest.ends_with(".md")
+    && dest.trim_start_matches("crate::").split("::").all(|seg| {
+      let mut chars = seg.chars();
+      chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+    })
+}
+
+/// Whether an unresolved destination is unambiguous enough to be worth warning about.
+///
+/// A `crate::⋯`-prefixed or `::`-qualified path can only be a Rust item path, so failing to
+/// resolve it is worth a warning. A bare single word like `LICENSE` or `config` is just as likely
+/// to be an ordinary README link to a file of that name; silently leaving it alone avoids
+/// misfiring on every such link.
+fn looks_unambiguously_qualified(dest: &str) -> bool {
+  dest == "crate" || dest.contains("::")
+}
+
+/// Rewrite Rust intra-doc links (`[⋯](crate::⋯)`, `[bar()](Foo::bar)`, `[Type](module::Type)`,
+/// …) found in `doc` so that they point at their corresponding docs.rs page, or strip them down
+/// to plain text if `strip` is set.
+fn rewrite_intralinks(
+  doc: &str,
+  crate_name: &str,
+  entry_point: &Path,
+  strip: bool,
+  emit_warning: &mut impl FnMut(&str),
+) -> String {
+  let index = ItemIndex::from_source(&fs::read_to_string(entry_point).unwrap_or_default());
+  let link_re = Regex::new(r"\[([^\]]*)\]\(([^)\s]+)\)").unwrap();
+
+  link_re
+    .replace_all(doc, |caps: &regex::Captures| {
+      let text = &caps[1];
+      let dest = &caps[2];
+
+      if !looks_like_intralink(dest) {
+        return caps[0].to_owned();
+      }
+
+      if strip {
+        return text.to_owned();
+      }
+
+      let path = if dest == "crate" { "" } else { dest.strip_prefix("crate::").unwrap_or(dest) };
+      let segments: Vec<&str> = if path.is_empty() {
+        Vec::new()
+      } else {
+        path.split("::").collect()
+      };
+
+      if segments.is_empty() {
+        return format!("[{}](https://docs.rs/{}/latest/{})", text, crate_name, crate_name);
+      }
+
+      match index.resolve(&segments) {
+        Some((kind, item_path)) => {
+          let (item_name, parents) = item_path.split_last().unwrap();
+          let mut url = format!(
+            "https://docs.rs/{}/latest/{}/{}",
+            crate_name,
+            crate_name,
+            parents.iter().map(|s| format!("{}/", s)).collect::<String>()
+          );
+
+          url.push_str(&kind.page_file(item_name));
+
+          if item_path.len() < segments.len() {
+            let sub_item = segments.last().unwrap();
+
+            if index.is_associated_const(sub_item) {
+              url.push_str("#associatedconstant.");
+            } else {
+              url.push_str("#method.");
+            }
+
+            url.push_str(sub_item);
+          }
+
+          format!("[{}]({})", text, url)
+        }
+        None => {
+          if looks_unambiguously_qualified(dest) {
+            emit_warning(&format!("could not resolve intra-doc link `{}`", dest));
+          }
+
+          caps[0].to_owned()
+        }
+      }
+    })
+    .into_owned()
+}
+
+/// Rustdoc attribute tags that a fenced Rust code block may carry, beside a bare language tag.
+const RUST_FENCE_TAGS: &[&str] = &[
+  "rust",
+  "no_run",
+  "should_panic",
+  "ignore",
+  "compile_fail",
+  "edition2018",
+  "edition2021",
+];
+
+/// Whether a code fence info string (the text right after the opening ` ``` `) denotes a rustdoc
+/// Rust block, be it bare or decorated with rustdoc-only attributes.
+fn is_rustdoc_fence(info: &str) -> bool {
+  info.is_empty()
+    || info
+      .split(',')
+      .all(|tag| RUST_FENCE_TAGS.contains(&tag.trim()))
+}
+
+/// Rewrite rustdoc code fences in `doc` so they render nicely on GitHub:
+///
+///   - if `rewrite_fences` is set, bare ` ``` ` fences and rustdoc-attribute fences (` ```no_run `,
+///     ` ```should_panic `, …) become a plain ` ```rust ` fence;
+///   - if `unwrap_fn_main` is set, a fence whose whole content is a single `fn main() { ⋯ }` wrapper
+///     (the one rustdoc implicitly adds around bare expressions) is unwrapped to just its body.
+fn rewrite_code_fences(doc: &str, rewrite_fences: bool, unwrap_fn_main: bool) -> String {
+  if !rewrite_fences && !unwrap_fn_main {
+    return doc.to_owned();
+  }
+
+  let fence_re = Regex::new(r"^(\s*)(`{3,})(.*)$").unwrap();
+  let mut out = Vec::new();
+  let mut fence: Option<(String, String)> = None; // (indent, backticks) of the currently open fence
+  let mut block: Vec<&str> = Vec::new();
+
+  for line in doc.lines() {
+    match fence_re.captures(line) {
+      Some(caps) if fence.is_none() => {
+        let (indent, backticks, info) = (&caps[1], &caps[2], caps[3].trim());
+        let is_rust = is_rustdoc_fence(info);
+
+        fence = Some((indent.to_owned(), backticks.to_owned()));
+        block.clear();
+
+        if rewrite_fences && is_rust {
+          out.push(format!("{}{}rust", indent, backticks));
+        } else {
+          out.push(line.to_owned());
+        }
+      }
+
+      Some(caps) if Some(&caps[2]) == fence.as_ref().map(|(_, b)| b.as_str()) => {
+        fence = None;
+
+        let unwrapped = unwrap_fn_main
+          && block.len() >= 2
+          && block.first().unwrap().trim() == "fn main() {"
+          && block.last().unwrap().trim() == "}"
+          && block[1..block.len() - 1]
+            .iter()
+            .all(|l| l.trim().is_empty() || l.starts_with(char::is_whitespace));
+
+        if unwrapped {
+          for body_line in &block[1..block.len() - 1] {
+            out.push(body_line.strip_prefix("    ").unwrap_or(body_line).to_owned());
+          }
+        } else {
+          out.extend(block.iter().map(|l| l.to_string()));
+        }
+
+        out.push(line.to_owned());
+      }
+
+      _ => {
+        if fence.is_some() {
+          block.push(line);
+        } else {
+          out.push(line.to_owned());
+        }
+      }
+    }
+  }
+
+  if fence.is_some() {
+    out.extend(block.iter().map(|l| l.to_string()));
+  }
+
+  out.join(if doc.contains("\r\n") { "\r\n" } else { "\n" })
+}
+
+/// Transform the documentation extracted from the entry point and splice it into the README at
+/// the sync markers.
+pub fn transform_readme(
+  readme: &str,
+  doc: String,
+  crate_name: impl Into<String>,
+  entry_point: impl AsRef<Path>,
+  crlf: bool,
+  mut emit_warning: impl FnMut(&str),
+) -> Result<String, Error> {
+  transform_readme_with_options(
+    readme,
+    doc,
+    crate_name,
+    entry_point,
+    crlf,
+    false,
+    &mut emit_warning,
+  )
+}
+
+/// Like [`transform_readme`], but also supports stripping intra-doc links down to plain text
+/// instead of rewriting them to docs.rs, via `strip_intralinks`.
+pub fn transform_readme_with_options(
+  readme: &str,
+  doc: String,
+  crate_name: impl Into<String>,
+  entry_point: impl AsRef<Path>,
+  crlf: bool,
+  strip_intralinks: bool,
+  mut emit_warning: impl FnMut(&str),
+) -> Result<String, Error> {
+  transform_readme_full(
+    readme,
+    doc,
+    crate_name,
+    entry_point,
+    crlf,
+    strip_intralinks,
+    true,
+    false,
+    &mut emit_warning,
+  )
+}
+
+/// Like [`transform_readme_with_options`], with further control over the Rust code-block
+/// transformations applied when copying documentation into the README:
+///
+///   - `rewrite_fences`: rewrite bare or rustdoc-attribute code fences (` ```no_run `, `
+///     ```should_panic `, …) to a plain ` ```rust ` fence, so GitHub syntax-highlights them.
+///   - `unwrap_fn_main`: unwrap the implicit `fn main() { ⋯ }` rustdoc wraps single-expression
+///     examples in, so the README shows the bare example body.
+#[allow(clippy::too_many_arguments)]
+pub fn transform_readme_full(
+  readme: &str,
+  doc: String,
+  crate_name: impl Into<String>,
+  entry_point: impl AsRef<Path>,
+  crlf: bool,
+  strip_intralinks: bool,
+  rewrite_fences: bool,
+  unwrap_fn_main: bool,
+  mut emit_warning: impl FnMut(&str),
+) -> Result<String, Error> {
+  let crate_name = crate_name.into();
+  let newline = if crlf { "\r\n" } else { "\n" };
+
+  if doc.is_empty() {
+    emit_warning("no documentation found at the entry point");
+  }
+
+  let doc = rewrite_intralinks(
+    &doc,
+    &crate_name,
+    entry_point.as_ref(),
+    strip_intralinks,
+    &mut emit_warning,
+  );
+  let doc = rewrite_code_fences(&doc, rewrite_fences, unwrap_fn_main);
+
+  let new_block = format!(
+    "{}{}{}{}{}{}{}",
+    SYNC_START_MARKER, newline, doc, newline, newline, SYNC_END_MARKER, newline
+  );
+
+  if let (Some(start), Some(end)) = (readme.find(SYNC_START_MARKER), readme.find(SYNC_END_MARKER))
+  {
+    if start > end {
+      return Err(Error::MarkersMismatch);
+    }
+
+    let end = end + SYNC_END_MARKER.len();
+    Ok(format!("{}{}{}", &readme[..start], new_block, &readme[end..]))
+  } else if let Some(marker) = readme.find(SYNC_MARKER) {
+    let end = marker + SYNC_MARKER.len();
+    Ok(format!("{}{}{}", &readme[..marker], new_block, &readme[end..]))
+  } else {
+    Err(Error::MissingMarkers)
+  }
+}
+
+enum DiffOp<'a> {
+  Equal(&'a str),
+  Delete(&'a str),
+  Insert(&'a str),
+}
+
+/// Compute a minimal line-level diff between `old` and `new`, via a classic LCS table.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffOp<'a>> {
+  let (n, m) = (old.len(), new.len());
+  let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+
+  for i in (0..n).rev() {
+    for j in (0..m).rev() {
+      lcs[i][j] = if old[i] == new[j] {
+        lcs[i + 1][j + 1] + 1
+      } else {
+        lcs[i + 1][j].max(lcs[i][j + 1])
+      };
+    }
+  }
+
+  let mut ops = Vec::new();
+  let (mut i, mut j) = (0, 0);
+
+  while i < n && j < m {
+    if old[i] == new[j] {
+      ops.push(DiffOp::Equal(old[i]));
+      i += 1;
+      j += 1;
+    } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+      ops.push(DiffOp::Delete(old[i]));
+      i += 1;
+    } else {
+      ops.push(DiffOp::Insert(new[j]));
+      j += 1;
+    }
+  }
+
+  ops.extend(old[i..].iter().map(|l| DiffOp::Delete(l)));
+  ops.extend(new[j..].iter().map(|l| DiffOp::Insert(l)));
+
+  ops
+}
+
+/// Render a diff between `old` and `new` README contents according to `format`, or `None` if they
+/// are identical or `format` is [`DiffFormat::None`].
+pub fn format_readme_diff(old: &str, new: &str, format: DiffFormat) -> Option<String> {
+  if old == new || format == DiffFormat::None {
+    return None;
+  }
+
+  let old_lines: Vec<&str> = old.lines().collect();
+  let new_lines: Vec<&str> = new.lines().collect();
+  let ops = diff_lines(&old_lines, &new_lines);
+
+  match format {
+    DiffFormat::None => None,
+
+    DiffFormat::Brief => {
+      let added = ops.iter().filter(|op| matches!(op, DiffOp::Insert(_))).count();
+      let removed = ops.iter().filter(|op| matches!(op, DiffOp::Delete(_))).count();
+
+      Some(format!("{} line(s) added, {} line(s) removed", added, removed))
+    }
+
+    DiffFormat::Unified => {
+      const CONTEXT: usize = 3;
+      let mut out = String::new();
+      let pending: Vec<(char, &str)> = ops
+        .iter()
+        .map(|op| match *op {
+          DiffOp::Equal(line) => (' ', line),
+          DiffOp::Delete(line) => ('-', line),
+          DiffOp::Insert(line) => ('+', line),
+        })
+        .collect();
+
+      // Emit every changed line, but collapse runs of unchanged (context) lines down to
+      // `CONTEXT` lines on either side of the nearest change.
+      let mut idx = 0;
+
+      while idx < pending.len() {
+        if pending[idx].0 != ' ' {
+          let (sign, line) = pending[idx];
+          out.push(sign);
+          out.push_str(line);
+          out.push('\n');
+          idx += 1;
+          continue;
+        }
+
+        let start = idx;
+        while idx < pending.len() && pending[idx].0 == ' ' {
+          idx += 1;
+        }
+        let run = &pending[start..idx];
+        let keep_before = if start == 0 { 0 } else { CONTEXT.min(run.len()) };
+        let keep_after = if idx == pending.len() { 0 } else { CONTEXT.min(run.len()) };
+
+        if run.len() > keep_before + keep_after {
+          for (_, line) in &run[..keep_before] {
+            out.push(' ');
+            out.push_str(line);
+            out.push('\n');
+          }
+          out.push_str("⋯\n");
+          for (_, line) in &run[run.len() - keep_after..] {
+            out.push(' ');
+            out.push_str(line);
+            out.push('\n');
+          }
+        } else {
+          for (_, line) in run {
+            out.push(' ');
+            out.push_str(line);
+            out.push('\n');
+          }
+        }
+      }
+
+      Some(out)
+    }
+  }
+}
+
+/// Expand `{{crate_⋯}}` placeholder tokens in `text` using metadata already parsed from
+/// `manifest`’s `Cargo.toml`. Supported placeholders are `{{crate_name}}`, `{{crate_version}}`,
+/// `{{crate_license}}`, `{{crate_authors}}`, `{{crate_repository}}` and `{{crate_msrv}}`.
+/// Placeholders whose field isn’t set in the manifest are left untouched.
+///
+/// This should be applied to the documentation extracted from the entry point *before* it is
+/// spliced into the README, not to the rendered README itself: the README is rewritten in place on
+/// every sync, so expanding a placeholder there would destroy its token and leave the expanded
+/// value frozen forever, unable to ever be re-expanded again.
+pub fn expand_crate_placeholders(text: &str, manifest: &Manifest) -> String {
+  let re = Regex::new(r"\{\{\s*crate_([a-z_]+)\s*\}\}").unwrap();
+
+  re.replace_all(text, |caps: &regex::Captures| {
+    let value = match &caps[1] {
+      "name" => manifest.crate_name(),
+      "version" => manifest.crate_version().map(str::to_owned),
+      "license" => manifest.crate_license().map(str::to_owned),
+      "authors" => Some(manifest.crate_authors().join(", ")).filter(|s| !s.is_empty()),
+      "repository" => manifest.crate_repository().map(str::to_owned),
+      "msrv" => manifest.crate_msrv().map(str::to_owned),
+      _ => None,
+    };
+
+    value.unwrap_or_else(|| caps[0].to_owned())
+  })
+  .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Create a fresh scratch directory under the system temp dir, scoped to `name`, for tests that
+  /// need to lay out actual `Cargo.toml` files on disk.
+  fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cargo-sync-readme-test-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  #[test]
+  fn workspace_root_with_package_includes_both_root_and_member() {
+    let root = scratch_dir("workspace-hybrid");
+
+    fs::write(
+      root.join("Cargo.toml"),
+      r#"
+        [package]
+        name = "root-crate"
+        version = "0.1.0"
+
+        [workspace]
+        members = ["member"]
+      "#,
+    )
+    .unwrap();
+
+    let member_dir = root.join("member");
+    fs::create_dir_all(&member_dir).unwrap();
+    fs::write(
+      member_dir.join("Cargo.toml"),
+      r#"
+        [package]
+        name = "member-crate"
+        version = "0.1.0"
+      "#,
+    )
+    .unwrap();
+
+    let manifest = Manifest::find_manifest(&root).unwrap();
+
+    assert!(!manifest.is_virtual());
+    let members = manifest.members().unwrap();
+    let mut names: Vec<_> = members.iter().filter_map(Manifest::crate_name).collect();
+    names.sort();
+    assert_eq!(names, vec!["member-crate", "root-crate"]);
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn workspace_members_glob_expands_to_subdirectories_with_a_manifest() {
+    let root = scratch_dir("workspace-glob");
+
+    fs::write(
+      root.join("Cargo.toml"),
+      r#"
+        [workspace]
+        members = ["crates/*"]
+      "#,
+    )
+    .unwrap();
+
+    let crates_dir = root.join("crates");
+    fs::create_dir_all(&crates_dir).unwrap();
+
+    for name in ["alpha", "beta"] {
+      let crate_dir = crates_dir.join(name);
+      fs::create_dir_all(&crate_dir).unwrap();
+      fs::write(
+        crate_dir.join("Cargo.toml"),
+        format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", name),
+      )
+      .unwrap();
+    }
+
+    // Not a crate: has no Cargo.toml, so it must not be picked up by the glob.
+    fs::create_dir_all(crates_dir.join("not-a-crate")).unwrap();
+
+    let manifest = Manifest::find_manifest(&root).unwrap();
+
+    let mut names: Vec<_> =
+      manifest.members().unwrap().iter().filter_map(Manifest::crate_name).collect();
+    names.sort();
+    assert_eq!(names, vec!["alpha", "beta"]);
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn virtual_workspace_has_no_crate_name() {
+    let root = scratch_dir("workspace-virtual");
+
+    fs::write(
+      root.join("Cargo.toml"),
+      r#"
+        [workspace]
+        members = []
+      "#,
+    )
+    .unwrap();
+
+    let manifest = Manifest::find_manifest(&root).unwrap();
+
+    assert!(manifest.is_virtual());
+    assert_eq!(manifest.crate_name(), None);
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn looks_like_intralink_rejects_dotted_segments() {
+    assert!(!looks_like_intralink("config.toml"));
+    assert!(!looks_like_intralink("image.png"));
+  }
+
+  #[test]
+  fn looks_like_intralink_accepts_rust_paths() {
+    assert!(looks_like_intralink("Foo"));
+    assert!(looks_like_intralink("Foo::bar"));
+    assert!(looks_like_intralink("crate::module::Type"));
+  }
+
+  #[test]
+  fn looks_unambiguously_qualified_requires_a_path_separator() {
+    assert!(!looks_unambiguously_qualified("LICENSE"));
+    assert!(!looks_unambiguously_qualified("Foo"));
+    assert!(looks_unambiguously_qualified("crate"));
+    assert!(looks_unambiguously_qualified("Foo::bar"));
+    assert!(looks_unambiguously_qualified("crate::Foo"));
+  }
+
+  #[test]
+  fn rewrite_intralinks_does_not_warn_on_an_unresolved_bare_word() {
+    let root = scratch_dir("bare-word-intralink");
+    let entry_point = root.join("lib.rs");
+    fs::write(&entry_point, "// no public items here\n").unwrap();
+
+    let mut warnings = Vec::new();
+    let out = rewrite_intralinks(
+      "See the [LICENSE](LICENSE) file.",
+      "mycrate",
+      &entry_point,
+      false,
+      &mut |msg: &str| warnings.push(msg.to_owned()),
+    );
+
+    assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+    assert_eq!(out, "See the [LICENSE](LICENSE) file.");
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn rewrite_intralinks_still_warns_on_an_unresolved_qualified_path() {
+    let root = scratch_dir("qualified-unresolved-intralink");
+    let entry_point = root.join("lib.rs");
+    fs::write(&entry_point, "// no public items here\n").unwrap();
+
+    let mut warnings = Vec::new();
+    let _ = rewrite_intralinks(
+      "See [Foo::bar](Foo::bar).",
+      "mycrate",
+      &entry_point,
+      false,
+      &mut |msg: &str| warnings.push(msg.to_owned()),
+    );
+
+    assert_eq!(warnings.len(), 1);
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn rewrite_intralinks_points_modules_at_their_index_page() {
+    let root = scratch_dir("mod-intralink");
+    let entry_point = root.join("lib.rs");
+    fs::write(&entry_point, "pub mod foo;\n").unwrap();
+
+    let mut warnings = Vec::new();
+    let out = rewrite_intralinks(
+      "See the [foo module](foo).",
+      "mycrate",
+      &entry_point,
+      false,
+      &mut |msg: &str| warnings.push(msg.to_owned()),
+    );
+
+    assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+    assert!(
+      out.contains("https://docs.rs/mycrate/latest/mycrate/foo/index.html"),
+      "unexpected output: {}",
+      out
+    );
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn rewrite_intralinks_links_associated_constants_to_the_right_fragment() {
+    let root = scratch_dir("assoc-const-intralink");
+    let entry_point = root.join("lib.rs");
+    fs::write(
+      &entry_point,
+      "pub trait Foo {\n  const BAR: u8;\n  fn baz();\n}\n",
+    )
+    .unwrap();
+
+    let mut warnings = Vec::new();
+    let out = rewrite_intralinks(
+      "See [BAR](Foo::BAR) and [baz()](Foo::baz).",
+      "mycrate",
+      &entry_point,
+      false,
+      &mut |msg: &str| warnings.push(msg.to_owned()),
+    );
+
+    assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+    assert!(
+      out.contains("trait.Foo.html#associatedconstant.BAR"),
+      "unexpected output: {}",
+      out
+    );
+    assert!(out.contains("trait.Foo.html#method.baz"), "unexpected output: {}", out);
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn rewrite_intralinks_strip_drops_links_down_to_plain_text() {
+    let root = scratch_dir("strip-intralink");
+    let entry_point = root.join("lib.rs");
+    fs::write(&entry_point, "pub struct Foo;\n").unwrap();
+
+    let mut warnings = Vec::new();
+    let out = rewrite_intralinks(
+      "See [Foo](Foo) and the [LICENSE](LICENSE) file.",
+      "mycrate",
+      &entry_point,
+      true,
+      &mut |msg: &str| warnings.push(msg.to_owned()),
+    );
+
+    assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+    assert_eq!(out, "See Foo and the LICENSE file.");
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn extract_inner_doc_preserves_markdown_hard_breaks() {
+    let root = scratch_dir("doc-hard-break");
+    let entry_point = root.join("lib.rs");
+    fs::write(&entry_point, "//! Line one  \n//! Line two\n").unwrap();
+
+    let doc = extract_inner_doc(&entry_point, false, false);
+
+    assert_eq!(doc, "Line one  \nLine two");
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn extract_inner_doc_handles_nested_parens_in_cfg_attr_predicate() {
+    let root = scratch_dir("doc-cfg-attr-nested");
+    let entry_point = root.join("lib.rs");
+    fs::write(
+      &entry_point,
+      concat!(
+        "//! base doc\n",
+        "#![cfg_attr(any(feature = \"a\", feature = \"b\"), doc = \"extra doc\")]\n",
+        "//! trailing doc\n",
+      ),
+    )
+    .unwrap();
+
+    let doc = extract_inner_doc(&entry_point, false, false);
+
+    assert_eq!(doc, "base doc\nextra doc\ntrailing doc");
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn extract_inner_doc_with_cfg_matches_a_name_inside_any() {
+    let root = scratch_dir("doc-cfg-attr-any-active");
+    let entry_point = root.join("lib.rs");
+    fs::write(
+      &entry_point,
+      concat!(
+        "//! base doc\n",
+        "#![cfg_attr(any(feature = \"a\", feature = \"b\"), doc = \"extra doc\")]\n",
+      ),
+    )
+    .unwrap();
+
+    let active = vec!["b".to_owned()];
+    let doc = extract_inner_doc_with_cfg(&entry_point, false, false, &active);
+
+    assert_eq!(doc, "base doc\nextra doc");
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn extract_inner_doc_with_cfg_excludes_an_any_with_no_matching_name() {
+    let root = scratch_dir("doc-cfg-attr-any-inactive");
+    let entry_point = root.join("lib.rs");
+    fs::write(
+      &entry_point,
+      concat!(
+        "//! base doc\n",
+        "#![cfg_attr(any(feature = \"a\", feature = \"b\"), doc = \"extra doc\")]\n",
+      ),
+    )
+    .unwrap();
+
+    let active = vec!["c".to_owned()];
+    let doc = extract_inner_doc_with_cfg(&entry_point, false, false, &active);
+
+    assert_eq!(doc, "base doc");
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn extract_inner_doc_with_cfg_handles_not_and_all() {
+    let root = scratch_dir("doc-cfg-attr-not-all");
+    let entry_point = root.join("lib.rs");
+    fs::write(
+      &entry_point,
+      concat!(
+        "//! base doc\n",
+        "#![cfg_attr(all(feature = \"a\", not(feature = \"b\")), doc = \"extra doc\")]\n",
+      ),
+    )
+    .unwrap();
+
+    let active = vec!["a".to_owned()];
+    let doc = extract_inner_doc_with_cfg(&entry_point, false, false, &active);
+
+    assert_eq!(doc, "base doc\nextra doc");
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn rewrite_code_fences_rewrites_rustdoc_attribute_fences() {
+    let doc = "```no_run\nfn main() {}\n```";
+
+    let out = rewrite_code_fences(doc, true, false);
+
+    assert_eq!(out, "```rust\nfn main() {}\n```");
+  }
+
+  #[test]
+  fn rewrite_code_fences_unwraps_implicit_fn_main() {
+    let doc = "```\nfn main() {\n    let x = 1;\n}\n```";
+
+    let out = rewrite_code_fences(doc, false, true);
+
+    assert_eq!(out, "```\nlet x = 1;\n```");
+  }
+
+  #[test]
+  fn rewrite_code_fences_leaves_non_rust_fences_alone() {
+    let doc = "```text\nsome text\n```";
+
+    let out = rewrite_code_fences(doc, true, true);
+
+    assert_eq!(out, doc);
+  }
+
+  #[test]
+  fn format_readme_diff_none_is_always_none() {
+    assert_eq!(format_readme_diff("a\n", "b\n", DiffFormat::None), None);
+  }
+
+  #[test]
+  fn format_readme_diff_identical_is_none() {
+    assert_eq!(format_readme_diff("same\n", "same\n", DiffFormat::Brief), None);
+  }
+
+  #[test]
+  fn format_readme_diff_brief_counts_added_and_removed_lines() {
+    let diff = format_readme_diff("a\nb\nc\n", "a\nc\nd\n", DiffFormat::Brief).unwrap();
+
+    assert_eq!(diff, "1 line(s) added, 1 line(s) removed");
+  }
+
+  #[test]
+  fn format_readme_diff_unified_marks_changed_lines() {
+    let diff = format_readme_diff("a\nb\nc\n", "a\nx\nc\n", DiffFormat::Unified).unwrap();
+
+    assert!(diff.contains("-b"));
+    assert!(diff.contains("+x"));
+    assert!(diff.contains(" a"));
+  }
+
+  #[test]
+  fn expand_crate_placeholders_is_re_expandable_across_runs() {
+    let root = scratch_dir("placeholders");
+
+    fs::write(
+      root.join("Cargo.toml"),
+      r#"
+        [package]
+        name = "mycrate"
+        version = "0.1.0"
+      "#,
+    )
+    .unwrap();
+    let manifest = Manifest::find_manifest(&root).unwrap();
+
+    let doc = "Version: {{crate_version}}";
+    let expanded = expand_crate_placeholders(doc, &manifest);
+
+    assert_eq!(expanded, "Version: 0.1.0");
+    // Unlike expanding the rendered README in place, expanding the extracted doc text leaves the
+    // source-of-truth token untouched, so a later run with a bumped version re-expands correctly.
+    assert_eq!(doc, "Version: {{crate_version}}");
+
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn expand_crate_placeholders_leaves_unset_fields_untouched() {
+    let root = scratch_dir("placeholders-unset");
+
+    fs::write(
+      root.join("Cargo.toml"),
+      r#"
+        [package]
+        name = "mycrate"
+      "#,
+    )
+    .unwrap();
+    let manifest = Manifest::find_manifest(&root).unwrap();
+
+    let expanded = expand_crate_placeholders("License: {{crate_license}}", &manifest);
+
+    assert_eq!(expanded, "License: {{crate_license}}");
+
+    let _ = fs::remove_dir_all(&root);
+  }
+}
@@ -6,8 +6,9 @@
 //!
 //! Basically, this tool provides you with a simple mechanism to synchronize your front page
 //! documentation from your `lib.rs` or `main.rs` with a place in your *readme* file. In order to do
-//! so, this command will parse your inner documentation (i.e. `//!`) on `lib.rs` or `main.rs` and
-//! will output it in your *readme* file at specific markers.
+//! so, this command will parse your inner documentation — `//!` comments as well as `#![doc = "…"]`
+//! and `#![cfg_attr(…, doc = "…")]` attributes (including `#![doc = include_str!("…")]`) — on
+//! `lib.rs` or `main.rs` and will output it in your *readme* file at specific markers.
 //!
 //! ## The markers
 //!
@@ -72,19 +73,59 @@
 //!     the already present newlines but expect your document to be formatted with CRLF. If it’s
 //!     not then you will get punched in the face by a squirrel driving a motorcycle. Sorry. Also,
 //!     it will generate newlines with CRLF.
-//!   - `-c --check`: check whether the *readme* is synchronized.
+//!   - `-c --check`: check whether the *readme* is synchronized. On mismatch, this prints a diff
+//!     of what would change, controlled by `--diff-format`.
+//!   - `--diff-format <unified|brief|none>`: how to report a README found out of sync by
+//!     `--check` — a unified diff (the default), a one-line added/removed summary, or nothing.
+//!   - `--workspace`: synchronize the README of every crate in the workspace found in the current
+//!     directory, instead of assuming a single crate.
+//!   - `--package <name>`: restrict the synchronization (or the workspace check) to a single
+//!     workspace member.
+//!   - `--strip-intralinks`: instead of rewriting Rust intra-doc links to docs.rs, replace them
+//!     with their plain link text. Useful for READMEs rendered somewhere that won’t host the
+//!     crate on docs.rs.
+//!   - `--cfg <name>`: mark a cfg (typically a feature name) as active when deciding which
+//!     `#![cfg_attr(⋯, doc = ⋯)]` documentation to include. May be repeated. If you don’t pass any,
+//!     every `cfg_attr` doc is included regardless of its predicate.
+//!   - `--no-fence-rewrite`: by default, bare or rustdoc-attribute code fences (e.g. ` ```no_run `)
+//!     are rewritten to a plain ` ```rust ` fence so GitHub highlights them; pass this flag to keep
+//!     the README identical to what docs.rs would show.
+//!   - `--unwrap-fn-main`: unwrap the implicit `fn main() { ⋯ }` rustdoc wraps bare examples in, so
+//!     the README shows only the example body.
+//!
+//! ## Manifest placeholders
+//!
+//! Inside your documentation, you can use `{{crate_name}}`, `{{crate_version}}`,
+//! `{{crate_license}}`, `{{crate_authors}}`, `{{crate_repository}}` and `{{crate_msrv}}`; they are
+//! expanded with the corresponding field from your `Cargo.toml` each time the *readme* is
+//! synchronized. A placeholder whose field isn’t set in the manifest is left untouched. Only
+//! placeholders in the Rust documentation are expanded, not ones written directly in the *readme*
+//! outside the synchronized section: the *readme* is rewritten in place on every sync, so a
+//! placeholder expanded there would have its token destroyed and could never be re-expanded again.
 //!
 //! ## Intra-link support
 //!
 //! This tool rewrites intra-links so they point at the corresponding place in [docs.rs](https://docs.rs).
-//! At this point only intra-links of the form `[⋯](crate::⋯)` are supported.
+//! Both the `[⋯](crate::⋯)` form and plain rustdoc intra-doc links such as `[bar()](Foo::bar)` or
+//! `[Type](module::Type)` are supported; the item kind is inferred by scanning the entry point’s
+//! public items. Links that cannot be resolved this way are left untouched and reported as
+//! warnings, except a bare single word such as `[LICENSE](LICENSE)`, which is left untouched
+//! without a warning. Use `--strip-intralinks` if you’d rather drop them down to plain text.
 //!
 //! ## Q/A and troubleshooting
 //!
 //! ### Are workspace crates supported?
 //!
-//! Not yet! If you have ideas how the tool should behave with them, please contribute with an issue or
-//! a PR!
+//! Yes! If the manifest found in the current directory is a workspace (virtual or not), use
+//! `--workspace` to synchronize the README of every member, or `--package <name>` to target a
+//! single one. `--check` will report a non-zero exit code if any member’s README is out of sync.
+//! If the workspace root is itself a crate (it has both a `[package]` and a `[workspace]` section),
+//! its own README is included as a member too.
+//!
+//! A `members` entry of the form `some/dir/*` is expanded to every immediate subdirectory of
+//! `some/dir` that holds a `Cargo.toml`. Other glob shapes (`**`, a `*` in the middle of a path, …)
+//! aren’t supported and are treated as a literal path component, same as Cargo itself does for any
+//! glob it can’t expand.
 
 use std::env::current_dir;
 use std::fs::File;
@@ -93,7 +134,8 @@ use std::process;
 use structopt::StructOpt;
 
 use cargo_sync_readme::{
-  extract_inner_doc, read_readme, transform_readme, Manifest, PreferDocFrom,
+  expand_crate_placeholders, extract_inner_doc_with_cfg, format_readme_diff, read_readme,
+  transform_readme_full, DiffFormat, Manifest, PreferDocFrom,
 };
 
 #[derive(Debug, StructOpt)]
@@ -125,6 +167,49 @@ enum CliOpt {
 
     #[structopt(short, long, help = "Check whether the README is synchronized.")]
     check: bool,
+
+    #[structopt(
+      long,
+      help = "Synchronize the README of every member of the workspace found in the current directory."
+    )]
+    workspace: bool,
+
+    #[structopt(
+      long,
+      help = "Restrict the synchronization to a single workspace member, by crate name."
+    )]
+    package: Option<String>,
+
+    #[structopt(
+      long,
+      help = "Replace Rust intra-doc links with their link text instead of rewriting them to docs.rs."
+    )]
+    strip_intralinks: bool,
+
+    #[structopt(
+      long,
+      help = "Name of a cfg (e.g. a feature) considered active when resolving #![cfg_attr(⋯, doc = ⋯)] attributes. May be given multiple times; if omitted, every cfg_attr doc is included."
+    )]
+    cfg: Vec<String>,
+
+    #[structopt(
+      long,
+      help = "Don’t rewrite bare or rustdoc-attribute code fences (e.g. ```no_run) to a plain ```rust fence."
+    )]
+    no_fence_rewrite: bool,
+
+    #[structopt(
+      long,
+      help = "Unwrap the implicit `fn main() { … }` rustdoc wraps bare examples in, showing only the example body."
+    )]
+    unwrap_fn_main: bool,
+
+    #[structopt(
+      long,
+      default_value = "unified",
+      help = "How to report a README that is out of sync under --check: `unified`, `brief` or `none`."
+    )]
+    diff_format: DiffFormat,
   },
 }
 
@@ -136,62 +221,209 @@ files.
 If you’re in the special situation where your crate defines both a binary and a library, you should
 consider using the -f option to hint sync-readme which file it should read the documentation from.";
 
+/// Outcome of synchronizing a single crate’s README.
+enum SyncOutcome {
+  /// The README was already synchronized (or has just been rewritten).
+  InSync,
+  /// The README was out of sync (only reachable in `--check` mode).
+  OutOfSync,
+}
+
+/// Synchronize (or check) the README of a single, non-virtual manifest.
+///
+/// Returns the outcome of the synchronization together with whether any warnings were emitted
+/// along the way (always `false` in `--check` mode), or prints an error and returns `None` if something went wrong. Callers are
+/// responsible for turning warnings into a process exit code once every target has been
+/// processed, so that one member's warning doesn't stop the rest of a workspace from syncing.
+#[allow(clippy::too_many_arguments)]
+fn sync_manifest(
+  manifest: &Manifest,
+  prefer_doc_from: Option<PreferDocFrom>,
+  show_hidden_doc: bool,
+  crlf: bool,
+  check: bool,
+  strip_intralinks: bool,
+  cfg: &[String],
+  rewrite_fences: bool,
+  unwrap_fn_main: bool,
+  diff_format: DiffFormat,
+) -> Option<(SyncOutcome, bool)> {
+  let crate_name = match manifest.crate_name() {
+    None => {
+      eprintln!("Failed to get the name of the crate");
+      return None;
+    }
+    Some(name) => name,
+  };
+  let entry_point = manifest.entry_point(prefer_doc_from);
+
+  let entry_point = match entry_point {
+    Some(entry_point) => entry_point,
+    None => {
+      eprintln!("{}", CANNOT_FIND_ENTRY_POINT_ERR_STR);
+      return None;
+    }
+  };
+
+  let doc = extract_inner_doc_with_cfg(&entry_point, show_hidden_doc, crlf, cfg);
+  let doc = expand_crate_placeholders(&doc, manifest);
+  let readme_path = manifest.readme();
+  let mut had_warnings = false;
+  let emit_warning = |msg: &str| {
+    eprintln!("warning: {}", msg);
+    had_warnings = true;
+  };
+  let transformation = read_readme(&readme_path).and_then(|readme| {
+    transform_readme_full(
+      &readme,
+      doc,
+      crate_name,
+      entry_point,
+      crlf,
+      strip_intralinks,
+      rewrite_fences,
+      unwrap_fn_main,
+      emit_warning,
+    )
+    .map(|new| (readme, new))
+  });
+
+  match transformation {
+    Ok((ref old_readme, ref new_readme)) if check => {
+      if old_readme != new_readme {
+        eprintln!("README {} is not synchronized!", readme_path.display());
+
+        if let Some(diff) = format_readme_diff(old_readme, new_readme, diff_format) {
+          eprintln!("{}", diff);
+        }
+
+        Some((SyncOutcome::OutOfSync, false))
+      } else {
+        Some((SyncOutcome::InSync, false))
+      }
+    }
+
+    Ok((_, ref new_readme)) => {
+      let mut file = File::create(readme_path).unwrap();
+      let _ = file.write_all(new_readme.as_bytes());
+
+      Some((SyncOutcome::InSync, had_warnings))
+    }
+
+    Err(e) => {
+      eprintln!("{}", e);
+      None
+    }
+  }
+}
+
 fn main() {
   let CliOpt::SyncReadme {
     show_hidden_doc,
     prefer_doc_from,
     crlf,
     check,
+    workspace,
+    package,
+    strip_intralinks,
+    cfg,
+    no_fence_rewrite,
+    unwrap_fn_main,
+    diff_format,
   } = CliOpt::from_args();
+  let rewrite_fences = !no_fence_rewrite;
 
   if let Ok(pwd) = current_dir() {
     match Manifest::find_manifest(pwd) {
       Ok(ref manifest) => {
-        let crate_name = match manifest.crate_name() {
-          None => {
-            eprintln!("Failed to get the name of the crate");
+        let members = match manifest.members() {
+          Ok(members) => members,
+          Err(e) => {
+            eprintln!("{}", e);
             process::exit(1);
           }
-          Some(name) => name,
         };
-        let entry_point = manifest.entry_point(prefer_doc_from);
-
-        if let Some(entry_point) = entry_point {
-          let doc = extract_inner_doc(&entry_point, show_hidden_doc, crlf);
-          let readme_path = manifest.readme();
-          let mut had_warnings = false;
-          let emit_warning = |msg: &str| {
-            eprintln!("warning: {}", msg);
-            had_warnings = true;
-          };
-          let transformation = read_readme(&readme_path).and_then(|readme| {
-            transform_readme(&readme, doc, crate_name, entry_point, crlf, emit_warning)
-              .map(|new| (readme, new))
-          });
-
-          match transformation {
-            Ok((ref old_readme, ref new_readme)) if check => {
-              if old_readme != new_readme {
-                eprintln!("README is not synchronized!");
-                process::exit(1);
-              }
+
+        if !members.is_empty() {
+          if !workspace && package.is_none() {
+            eprintln!(
+              "This is a workspace; use --workspace to synchronize all members or --package \
+               <name> to target one."
+            );
+            process::exit(1);
+          }
+
+          let targets: Vec<_> = members
+            .iter()
+            .filter(|m| {
+              package
+                .as_ref()
+                .is_none_or(|name| m.crate_name().as_deref() == Some(name))
+            })
+            .collect();
+
+          if targets.is_empty() {
+            eprintln!("No workspace member matches --package {:?}", package.unwrap());
+            process::exit(1);
+          }
+
+          let mut any_out_of_sync = false;
+          let mut any_error = false;
+          let mut any_warnings = false;
+
+          for member in targets {
+            match sync_manifest(
+              member,
+              prefer_doc_from,
+              show_hidden_doc,
+              crlf,
+              check,
+              strip_intralinks,
+              &cfg,
+              rewrite_fences,
+              unwrap_fn_main,
+              diff_format,
+            ) {
+              Some((SyncOutcome::OutOfSync, _)) => any_out_of_sync = true,
+              Some((SyncOutcome::InSync, warned)) => any_warnings |= warned,
+              None => any_error = true,
             }
+          }
 
-            Ok((_, ref new_readme)) => {
-              let mut file = File::create(readme_path).unwrap();
-              let _ = file.write_all(new_readme.as_bytes());
+          if any_error {
+            process::exit(1);
+          }
+
+          if check && any_out_of_sync {
+            process::exit(1);
+          }
 
-              if had_warnings {
+          if any_warnings {
+            // Use code 2 for warnings.
+            process::exit(2);
+          }
+        } else {
+          match sync_manifest(
+            manifest,
+            prefer_doc_from,
+            show_hidden_doc,
+            crlf,
+            check,
+            strip_intralinks,
+            &cfg,
+            rewrite_fences,
+            unwrap_fn_main,
+            diff_format,
+          ) {
+            Some((SyncOutcome::OutOfSync, _)) => process::exit(1),
+            Some((SyncOutcome::InSync, warned)) => {
+              if warned {
                 // Use code 2 for warnings.
                 process::exit(2);
               }
             }
-
-            Err(e) => eprintln!("{}", e),
+            None => process::exit(1),
           }
-        } else {
-          eprintln!("{}", CANNOT_FIND_ENTRY_POINT_ERR_STR);
-          process::exit(1);
         }
       }
 